@@ -6,20 +6,25 @@ use std::env::var;
 
 fn main() {
     // Build the project insource, only building lib/libxmp.a
-    let dst = autotools::Config::new("libxmp")
+    let artifacts = autotools::Config::new("libxmp")
         .reconf("-v")
         .make_target("lib/libxmp.a")
         .insource(true)
         .build();
 
-    // Simply link the library without using pkg-config
-    println!("cargo:rustc-link-search=native={}", dst.join("lib").display());
+    // `make_target("lib/libxmp.a")` only builds the static lib in place; it
+    // never runs `make install`, so there's no installed `libxmp.pc` for
+    // `.emit_pkg_config()` to find. Link it manually instead.
+    println!(
+        "cargo:rustc-link-search=native={}",
+        artifacts.root().join("lib").display()
+    );
     println!("cargo:rustc-link-lib=static=xmp");
     println!("cargo:rustc-link-lib=c");
 
     // generate bindings using bindgen
     let bindings = bindgen::Builder::default()
-        .header("libxmp/include/xmp.h")
+        .header(artifacts.include_dir().join("xmp.h").to_string_lossy())
         .generate()
         .expect("unable to generate bindings");
 
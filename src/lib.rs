@@ -27,10 +27,10 @@
 //! use autotools;
 //!
 //! // Build the project in the path `foo` and installs it in `$OUT_DIR`
-//! let dst = autotools::build("foo");
+//! let artifacts = autotools::build("foo");
 //!
 //! // Simply link the library without using pkg-config
-//! println!("cargo:rustc-link-search=native={}", dst.display());
+//! println!("cargo:rustc-link-search=native={}", artifacts.root().display());
 //! println!("cargo:rustc-link-lib=static=foo");
 //! ```
 //!
@@ -48,15 +48,21 @@
 //! ```
 
 extern crate cc;
+extern crate jobserver;
+extern crate pkg_config;
 
-use std::collections::HashSet;
+use std::cell::Cell;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
 use std::env;
 use std::ffi::{OsStr, OsString};
 use std::fs;
+use std::hash::{Hash, Hasher};
 use std::io::ErrorKind;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::str;
+use std::time::UNIX_EPOCH;
 
 enum Kind {
     Enable,
@@ -66,6 +72,46 @@ enum Kind {
     Arbitrary,
 }
 
+// Walks `$PATH` looking for a program, caching each lookup so that checking
+// the same name twice with the same `Finder` is free. Modeled on rustbuild's
+// `Finder`.
+struct Finder {
+    cache: HashMap<OsString, Option<PathBuf>>,
+}
+
+impl Finder {
+    fn new() -> Finder {
+        Finder {
+            cache: HashMap::new(),
+        }
+    }
+
+    fn find(&mut self, name: &OsStr) -> Option<PathBuf> {
+        if let Some(cached) = self.cache.get(name) {
+            return cached.clone();
+        }
+
+        let found = env::var_os("PATH").and_then(|paths| {
+            env::split_paths(&paths).find_map(|dir| {
+                let candidate = dir.join(name);
+                if candidate.is_file() {
+                    return Some(candidate);
+                }
+                if cfg!(windows) {
+                    let with_exe = candidate.with_extension("exe");
+                    if with_exe.is_file() {
+                        return Some(with_exe);
+                    }
+                }
+                None
+            })
+        });
+
+        self.cache.insert(name.to_owned(), found.clone());
+        found
+    }
+}
+
 /// Builder style configuration for a pending autotools build.
 ///
 /// # Note
@@ -99,9 +145,9 @@ enum Kind {
 /// // into $OUT_DIR
 /// let mut cfg = autotools::Config::new("libfoo_source_directory");
 /// cfg.config_option("host", Some("i686-pc-windows-gnu"));
-/// let dst = cfg.build();
+/// let artifacts = cfg.build();
 ///
-/// println!("cargo:rustc-link-search=native={}", dst.display());
+/// println!("cargo:rustc-link-search=native={}", artifacts.root().display());
 /// println!("cargo:rustc-link-lib=static=foo");
 /// ```
 pub struct Config {
@@ -111,6 +157,7 @@ pub struct Config {
     cflags: OsString,
     cxxflags: OsString,
     ldflags: OsString,
+    cppflags: OsString,
     options: Vec<(Kind, OsString, Option<OsString>)>,
     target: Option<String>,
     make_args: Option<Vec<String>>,
@@ -122,6 +169,95 @@ pub struct Config {
     build_insource: bool,
     forbidden_args: HashSet<String>,
     fast_build: bool,
+    pkg_config_name: Option<String>,
+    try_system: Option<(String, Option<String>)>,
+    jobserver: bool,
+    ar: Option<OsString>,
+    ranlib: Option<OsString>,
+    nm: Option<OsString>,
+    strip: Option<OsString>,
+    linker: Option<OsString>,
+    emit_metadata: bool,
+    rerun_if_changed: bool,
+    clean_policy: CleanPolicy,
+    // Tracks whether `maybe_clear` has already made its clean/no-clean
+    // decision for this build, since `try_get_paths` (and thus
+    // `maybe_clear`) is called several times over the course of a single
+    // `build()` -- we only want `make clean` to run (at most) once, and
+    // only ever before `configure` regenerates the Makefile.
+    cleaned: Cell<bool>,
+}
+
+/// Controls when [`Config::build`] runs `make clean` in the build directory
+/// before reconfiguring, to guard against an `OUT_DIR` reused across builds
+/// leaving behind stale configured artifacts that silently poison the next
+/// `make`.
+///
+/// This applies to out-of-source builds only; the source directory of an
+/// [`insource`](Config::insource) build is never cleaned.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CleanPolicy {
+    /// Never run `make clean`; reuse whatever is in the build directory.
+    /// This is the default.
+    Never,
+    /// Run `make clean` only when the tracked source tree has changed since
+    /// the last build (tracked the same way as [`fast_build`](Config::fast_build)).
+    OnSourceChange,
+    /// Always run `make clean` before reconfiguring.
+    Always,
+}
+
+/// Which strategy `Config::build` ended up taking.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BuildKind {
+    /// A pre-installed copy of the library was found via `pkg-config` and
+    /// used as-is; `configure`/`make` were never invoked.
+    System,
+    /// The vendored source tree was configured and built.
+    Source,
+    /// The user pointed us at an existing installation with `<PREFIX>_NO_BUILD`
+    /// and friends; `configure`/`make` were never invoked.
+    Prebuilt,
+}
+
+/// The result of a successful [`Config::build`].
+///
+/// Records [`which strategy`](BuildKind) was used and describes the
+/// resulting installation (its root, include and lib directories).
+pub struct Artifacts {
+    kind: BuildKind,
+    root: PathBuf,
+    include_dir: PathBuf,
+    lib_dir: PathBuf,
+}
+
+impl Artifacts {
+    /// Whether the system library was reused, a prebuilt installation was
+    /// used, or the vendored source was built.
+    pub fn kind(&self) -> BuildKind {
+        self.kind
+    }
+
+    /// The directory the library was installed into (for [`BuildKind::Source`]),
+    /// or the parent of the first `pkg-config`-reported link path (for
+    /// [`BuildKind::System`]).
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// The directory holding the installed headers (`$root/include`, or the
+    /// first `pkg-config`-reported include path for a [`BuildKind::System`]
+    /// build). Feed this straight into `bindgen::Builder::clang_arg`.
+    pub fn include_dir(&self) -> &Path {
+        &self.include_dir
+    }
+
+    /// The directory holding the installed libraries (`$root/lib`, or the
+    /// first `pkg-config`-reported link path for a [`BuildKind::System`]
+    /// build).
+    pub fn lib_dir(&self) -> &Path {
+        &self.lib_dir
+    }
 }
 
 /// Builds the native library rooted at `path` with the default configure options.
@@ -134,13 +270,13 @@ pub struct Config {
 ///
 /// // Builds the project in the directory located in `libfoo`, installing it
 /// // into $OUT_DIR
-/// let dst = autotools::build("libfoo");
+/// let artifacts = autotools::build("libfoo");
 ///
-/// println!("cargo:rustc-link-search=native={}", dst.display());
+/// println!("cargo:rustc-link-search=native={}", artifacts.root().display());
 /// println!("cargo:rustc-link-lib=static=foo");
 /// ```
 ///
-pub fn build<P: AsRef<Path>>(path: P) -> PathBuf {
+pub fn build<P: AsRef<Path>>(path: P) -> Artifacts {
     Config::new(path.as_ref()).build()
 }
 
@@ -201,6 +337,7 @@ impl Config {
             cflags: OsString::new(),
             cxxflags: OsString::new(),
             ldflags: OsString::new(),
+            cppflags: OsString::new(),
             options: Vec::new(),
             make_args: None,
             make_targets: None,
@@ -212,6 +349,18 @@ impl Config {
             build_insource: false,
             forbidden_args: HashSet::new(),
             fast_build: false,
+            pkg_config_name: None,
+            try_system: None,
+            jobserver: true,
+            ar: None,
+            ranlib: None,
+            nm: None,
+            strip: None,
+            linker: None,
+            emit_metadata: false,
+            rerun_if_changed: true,
+            clean_policy: CleanPolicy::Never,
+            cleaned: Cell::new(false),
         })
     }
 
@@ -245,6 +394,15 @@ impl Config {
         self
     }
 
+    /// By default, the spawned `make` inherits Cargo's jobserver token pool
+    /// (via `CARGO_MAKEFLAGS`) so the native build cooperates with Cargo's
+    /// global `-j` limit instead of building single-threaded. Call this to
+    /// opt back out and fall back to an explicit `-j$NUM_JOBS` instead.
+    pub fn disable_jobserver(&mut self) -> &mut Config {
+        self.jobserver = false;
+        self
+    }
+
     fn set_opt<P: AsRef<OsStr>>(&mut self, kind: Kind, opt: P, optarg: Option<P>) -> &mut Config {
         let optarg = optarg.as_ref().map(|v| v.as_ref().to_owned());
         self.options.push((kind, opt.as_ref().to_owned(), optarg));
@@ -311,6 +469,38 @@ impl Config {
         self
     }
 
+    /// Adds a custom preprocessor flag to pass down via `$CPPFLAGS`,
+    /// supplementing those that this library already passes.
+    ///
+    /// Default flags have lowest priority, then any flags from the
+    /// environment variable `$CPPFLAGS`, then any flags specified with this
+    /// method (matching [`cflag`](#method.cflag)'s precedence). Since
+    /// `CPPFLAGS` is honored by both the C and C++ compilation rules in
+    /// standard autotools makefiles, prefer this (and [`define`](#method.define))
+    /// over [`cflag`](#method.cflag) for include paths (`-I`) and macros
+    /// that should also reach C++ sources.
+    pub fn cppflag<P: AsRef<OsStr>>(&mut self, flag: P) -> &mut Config {
+        self.cppflags.push(" ");
+        self.cppflags.push(flag.as_ref());
+        self
+    }
+
+    /// Adds `-D<key>` or `-D<key>=<value>` to `$CPPFLAGS`, mirroring the `cc`
+    /// crate's `Build::define`.
+    pub fn define<K, V>(&mut self, key: K, value: Option<V>) -> &mut Config
+    where
+        K: AsRef<OsStr>,
+        V: AsRef<OsStr>,
+    {
+        let mut define = OsString::from("-D");
+        define.push(key.as_ref());
+        if let Some(value) = value {
+            define.push("=");
+            define.push(value.as_ref());
+        }
+        self.cppflag(define)
+    }
+
     /// Sets the target triple for this compilation.
     ///
     /// This is automatically scraped from `$TARGET` which is set for Cargo
@@ -335,6 +525,40 @@ impl Config {
         self
     }
 
+    /// Overrides the archiver (`$AR`) passed to `configure`.
+    ///
+    /// Without this, `Config` uses `$AR` from the environment, falling back
+    /// to `<target>-ar` (built from the target triple) when cross-compiling,
+    /// and finally to plain `ar`.
+    pub fn ar<P: AsRef<OsStr>>(&mut self, ar: P) -> &mut Config {
+        self.ar = Some(ar.as_ref().to_owned());
+        self
+    }
+
+    /// Overrides the archive indexer (`$RANLIB`) passed to `configure`. See [`ar`](#method.ar).
+    pub fn ranlib<P: AsRef<OsStr>>(&mut self, ranlib: P) -> &mut Config {
+        self.ranlib = Some(ranlib.as_ref().to_owned());
+        self
+    }
+
+    /// Overrides the symbol-table tool (`$NM`) passed to `configure`. See [`ar`](#method.ar).
+    pub fn nm<P: AsRef<OsStr>>(&mut self, nm: P) -> &mut Config {
+        self.nm = Some(nm.as_ref().to_owned());
+        self
+    }
+
+    /// Overrides the strip tool (`$STRIP`) passed to `configure`. See [`ar`](#method.ar).
+    pub fn strip<P: AsRef<OsStr>>(&mut self, strip: P) -> &mut Config {
+        self.strip = Some(strip.as_ref().to_owned());
+        self
+    }
+
+    /// Overrides the linker (`$LD`) passed to `configure`. See [`ar`](#method.ar).
+    pub fn linker<P: AsRef<OsStr>>(&mut self, linker: P) -> &mut Config {
+        self.linker = Some(linker.as_ref().to_owned());
+        self
+    }
+
     /// Sets the output directory for this compilation.
     ///
     /// This is automatically scraped from `$OUT_DIR` which is set for Cargo
@@ -404,13 +628,132 @@ impl Config {
         self
     }
 
-    /// Enable fast building (which skips over configure if there is no)
-    /// change in the configuration parameters.
+    /// By default, `build()` scans the source tree for the files that
+    /// actually drive an autotools build (`configure.ac`, `Makefile.am`,
+    /// `configure`, `*.c`/`*.h`, and similar) and emits a
+    /// `cargo:rerun-if-changed` line for each, plus
+    /// `cargo:rerun-if-env-changed` for the toolchain variables this crate
+    /// reads (`CFLAGS`, `CXXFLAGS`, `LDFLAGS`, `TARGET`, `HOST`, `MAKE`,
+    /// `NUM_JOBS`, `CARGO_MAKEFLAGS`). Call this to opt out.
+    pub fn disable_rerun_if_changed(&mut self) -> &mut Config {
+        self.rerun_if_changed = false;
+        self
+    }
+
+    /// Sets the policy for running `make clean` in the build directory
+    /// before reconfiguring. Defaults to [`CleanPolicy::Never`].
+    pub fn clean_policy(&mut self, policy: CleanPolicy) -> &mut Config {
+        self.clean_policy = policy;
+        self
+    }
+
+    /// Enable fast building: skip `configure` if its parameters haven't
+    /// changed since the last run, and skip `make` entirely as well when
+    /// nothing under the source tree has changed either (tracked by hashing
+    /// each file's path, size and mtime). The `cargo:rerun-if-changed` lines
+    /// are emitted independently of this setting; see
+    /// [`disable_rerun_if_changed`](#method.disable_rerun_if_changed).
     pub fn fast_build(&mut self, fast: bool) -> &mut Config {
         self.fast_build = fast;
         self
     }
 
+    /// Automatically print the `cargo:rustc-link-search` and `cargo:rustc-link-lib`
+    /// lines after a successful `build()`, by querying the `.pc` file that `name`
+    /// installs into `$dst/lib/pkgconfig`.
+    ///
+    /// This points `PKG_CONFIG_PATH` at the freshly built prefix (without
+    /// disturbing any system `PKG_CONFIG_PATH` already set, so `Requires`
+    /// on system libraries still resolve) and asks `pkg-config` for the link
+    /// flags, requesting `--static` resolution (which pulls in `Libs.private`
+    /// and the transitive closure of `Requires.private`) whenever
+    /// [`enable_static`](#method.enable_static) is set and
+    /// [`enable_shared`](#method.enable_shared) is not.
+    pub fn emit_pkg_config(&mut self, name: &str) -> &mut Config {
+        self.pkg_config_name = Some(name.to_owned());
+        self
+    }
+
+    /// A more general alternative to [`emit_pkg_config`](#method.emit_pkg_config)
+    /// that doesn't need to be told the package name: after a successful
+    /// build it finds every `.pc` file under the install prefix's
+    /// `lib/pkgconfig` and `lib64/pkgconfig` directories, asks `pkg-config`
+    /// for each package's link flags (with `--static`, which adds
+    /// `Libs.private`, when static linking is enabled), and emits the
+    /// corresponding `cargo:rustc-link-*` lines, preferring `static=` when
+    /// [`enable_static`](#method.enable_static) is set and
+    /// [`enable_shared`](#method.enable_shared) is not.
+    ///
+    /// It also exports `PKG_CONFIG_PATH` pointing at the prefix, so a
+    /// downstream `pkg-config`-based probe finds the freshly built library.
+    /// When no `.pc` file is found, falls back to globbing `lib` for
+    /// `lib<name>.a`/`lib<name>.so` and emitting a best-effort link line.
+    pub fn emit_metadata(&mut self, emit: bool) -> &mut Config {
+        self.emit_metadata = emit;
+        self
+    }
+
+    /// Before building anything, probe for an already-installed system copy
+    /// of `name` via `pkg-config`, optionally requiring at least `version`.
+    ///
+    /// If the probe succeeds, `build()` emits the system library's link
+    /// metadata and returns immediately with [`BuildKind::System`], skipping
+    /// `reconf`, `configure` and `make` entirely. If it fails (not found, or
+    /// older than `version`), `build()` falls back to building the vendored
+    /// source tree as usual and returns [`BuildKind::Source`].
+    pub fn try_system<P: Into<String>>(&mut self, name: P, version: Option<&str>) -> &mut Config {
+        self.try_system = Some((name.into(), version.map(|v| v.to_owned())));
+        self
+    }
+
+    /// Shorthand for [`try_system`](#method.try_system) requiring at least
+    /// `version`, matching the `pkg_config::Config::atleast_version` naming
+    /// that `lzma-sys` and `libssh2-sys` build around.
+    pub fn atleast_pkgconfig_version(&mut self, name: &str, version: &str) -> &mut Config {
+        self.try_system(name, Some(version))
+    }
+
+    // Walk `$PATH` up front for every program this build is actually going to
+    // need, so a missing tool is reported as a single actionable error
+    // before we spend any time configuring, rather than surfacing as an
+    // opaque `ErrorKind::NotFound` from whichever command happens to run
+    // first.
+    fn preflight_tools(&self) -> Result<(), String> {
+        let mut finder = Finder::new();
+        let mut missing = Vec::new();
+
+        if finder.find(OsStr::new("sh")).is_none() {
+            missing.push("sh".to_owned());
+        }
+
+        let target = self
+            .target
+            .clone()
+            .or_else(|| env::var("TARGET").ok())
+            .unwrap_or_default();
+        let make = if target.contains("emscripten") {
+            "emmake".to_owned()
+        } else {
+            env::var("MAKE").unwrap_or_else(|_| "make".to_owned())
+        };
+        if finder.find(OsStr::new(&make)).is_none() {
+            missing.push(make);
+        }
+
+        if self.reconfig.is_some() && finder.find(OsStr::new("autoreconf")).is_none() {
+            missing.push("autoreconf".to_owned());
+        }
+
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(format!(
+                "missing required tool(s) on $PATH: {}",
+                missing.join(", ")
+            ))
+        }
+    }
+
     fn try_get_paths(&self) -> Result<(PathBuf, PathBuf), String> {
         if self.build_insource {
             let dst = self.path.clone();
@@ -422,12 +765,55 @@ impl Config {
                 None => PathBuf::from(try_getenv_unwrap("OUT_DIR")?),
             };
             let build = dst.join("build");
-            self.maybe_clear(&build);
+            self.maybe_clear(&build)?;
             let _ = fs::create_dir(&build);
             Ok((dst, build))
         }
     }
 
+    // Derives the environment variable prefix used for the `<PREFIX>_NO_BUILD` /
+    // `<PREFIX>_LIB_DIR` / `<PREFIX>_INCLUDE_DIR` overrides below, from the
+    // final component of `self.path` (e.g. `libfoo` -> `LIBFOO`), mirroring
+    // how crates like `capstone-sys` name their override variables.
+    fn env_prefix(&self) -> String {
+        self.path
+            .file_name()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default()
+            .to_uppercase()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect()
+    }
+
+    // If `<PREFIX>_NO_BUILD` is set, skip the autotools run entirely and
+    // build `Artifacts` from `<PREFIX>_LIB_DIR`/`<PREFIX>_INCLUDE_DIR`
+    // instead, so a user (or a CI image with the dependency preinstalled)
+    // can point us straight at it.
+    fn try_prebuilt(&self) -> Result<Option<Artifacts>, String> {
+        let prefix = self.env_prefix();
+        if env::var_os(format!("{}_NO_BUILD", prefix)).is_none() {
+            return Ok(None);
+        }
+
+        let lib_dir = PathBuf::from(try_getenv_unwrap(&format!("{}_LIB_DIR", prefix))?);
+        let include_dir = PathBuf::from(try_getenv_unwrap(&format!("{}_INCLUDE_DIR", prefix))?);
+        let root = lib_dir
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| lib_dir.clone());
+
+        println!("cargo:root={}", root.display());
+        println!("cargo:include={}", include_dir.display());
+
+        Ok(Some(Artifacts {
+            kind: BuildKind::Prebuilt,
+            root,
+            include_dir,
+            lib_dir,
+        }))
+    }
+
     /// Run this configuration
     ///
     /// This will run only the build system generator.
@@ -555,6 +941,20 @@ impl Config {
         }
         cmd.env("CXXFLAGS", cxxflags);
 
+        let mut cppflags = OsString::new();
+        if let Some(flags) = env::var_os("CPPFLAGS") {
+            cppflags.push(&flags);
+        }
+        if !self.cppflags.is_empty() {
+            if !cppflags.is_empty() {
+                cppflags.push(" ");
+            }
+            cppflags.push(&self.cppflags);
+        }
+        if !cppflags.is_empty() {
+            cmd.env("CPPFLAGS", cppflags);
+        }
+
         if !self.ldflags.is_empty() {
             match env::var_os("LDFLAGS") {
                 None => cmd.env("LDFLAGS", &self.ldflags),
@@ -605,6 +1005,22 @@ impl Config {
         cmd.env("CC", cc_path);
         cmd.env("CXX", cxx_path);
 
+        let cross_prefix = if target != host { Some(&target) } else { None };
+        cmd.env("AR", resolve_binutil(&self.ar, "AR", cross_prefix, "ar"));
+        cmd.env(
+            "RANLIB",
+            resolve_binutil(&self.ranlib, "RANLIB", cross_prefix, "ranlib"),
+        );
+        cmd.env("NM", resolve_binutil(&self.nm, "NM", cross_prefix, "nm"));
+        cmd.env(
+            "STRIP",
+            resolve_binutil(&self.strip, "STRIP", cross_prefix, "strip"),
+        );
+        cmd.env(
+            "LD",
+            resolve_binutil(&self.linker, "LD", cross_prefix, "ld"),
+        );
+
         for (k, v) in c_compiler.env().iter().chain(&self.env) {
             cmd.env(k, v);
         }
@@ -648,14 +1064,46 @@ impl Config {
         Ok(dst)
     }
 
+    // Emits the `cargo:root=`/`cargo:include=` and link metadata derived from
+    // an already-built `dst`, and builds the resulting `Artifacts`. Shared by
+    // both the normal build path and the `fast_build` early-exit, since
+    // consumers rely on this metadata (e.g. bindgen picking up
+    // `include_dir()`, or downstream crates' `cargo:rustc-link-*`) being
+    // emitted on *every* `build()` call, not just the first one that
+    // actually runs `make`.
+    fn finish_build(&self, dst: PathBuf) -> Result<Artifacts, String> {
+        println!("cargo:root={}", dst.display());
+
+        let include_dir = dst.join("include");
+        let lib_dir = dst.join("lib");
+        println!("cargo:include={}", include_dir.display());
+
+        let is_static = self.enable_static && !self.enable_shared;
+
+        if let Some(ref name) = self.pkg_config_name {
+            emit_pkg_config_metadata(&dst, name, is_static)?;
+        }
+
+        if self.emit_metadata {
+            emit_metadata_for_prefix(&dst, is_static)?;
+        }
+
+        Ok(Artifacts {
+            kind: BuildKind::Source,
+            root: dst,
+            include_dir,
+            lib_dir,
+        })
+    }
+
     /// Run this configuration, compiling the library with all the configured
     /// options.
     ///
     /// This will run both the build system generator command as well as the
     /// command to build the library.
-    pub fn build(&mut self) -> PathBuf {
+    pub fn build(&mut self) -> Artifacts {
         match self.try_build() {
-            Ok(path) => path,
+            Ok(artifacts) => artifacts,
             Err(msg) => fail(&msg),
         }
     }
@@ -665,11 +1113,75 @@ impl Config {
     ///
     /// This will run both the build system generator command as well as the
     /// command to build the library. If it fails it will return an error message
-    pub fn try_build(&mut self) -> Result<PathBuf, String> {
+    pub fn try_build(&mut self) -> Result<Artifacts, String> {
+        if let Some(artifacts) = self.try_prebuilt()? {
+            return Ok(artifacts);
+        }
+
+        if let Some((name, version)) = self.try_system.clone() {
+            if let Some((root, include_dir, lib_dir)) =
+                try_system_probe(&name, version.as_deref())?
+            {
+                return Ok(Artifacts {
+                    kind: BuildKind::System,
+                    root,
+                    include_dir,
+                    lib_dir,
+                });
+            }
+        }
+
+        self.preflight_tools()?;
+
+        // When `fast_build` is enabled, fingerprint the source tree so we can
+        // tell whether anything relevant actually changed since the last run
+        // and potentially skip `make` entirely.
+        let fingerprint = if self.fast_build {
+            let (dst, build) = self.try_get_paths()?;
+            let (_, hash) = fingerprint_source(&self.path, &build);
+            Some((dst, build, hash))
+        } else {
+            None
+        };
+
+        if self.rerun_if_changed {
+            let (_, build) = self.try_get_paths()?;
+            for file in autotools_source_files(&self.path, &build) {
+                println!("cargo:rerun-if-changed={}", file.display());
+            }
+            for var in [
+                "CFLAGS",
+                "CXXFLAGS",
+                "LDFLAGS",
+                "TARGET",
+                "HOST",
+                "MAKE",
+                "NUM_JOBS",
+                "CARGO_MAKEFLAGS",
+            ] {
+                println!("cargo:rerun-if-env-changed={}", var);
+            }
+        }
+
+        if let Some((dst, build, hash)) = &fingerprint {
+            let fingerprint_file = build.join("source.fingerprint");
+            let unchanged = fs::read_to_string(fingerprint_file)
+                .ok()
+                .and_then(|s| s.trim().parse::<u64>().ok())
+                == Some(*hash);
+            if unchanged && build.join("Makefile").exists() {
+                return self.finish_build(dst.clone());
+            }
+        }
+
         self.try_configure()?;
 
         let (dst, build) = self.try_get_paths()?;
 
+        if let Some((_, _, hash)) = &fingerprint {
+            let _ = fs::write(build.join("source.fingerprint"), hash.to_string());
+        }
+
         let target = match self.target.clone() {
             Some(target) => target,
             None => try_getenv_unwrap("TARGET")?,
@@ -681,61 +1193,109 @@ impl Config {
         let mut program = "make";
         let mut cmd;
         let executable = env::var("MAKE").unwrap_or_else(|_| program.to_owned());
+        // Cargo's jobserver protocol is a GNU make extension; handing its
+        // `--jobserver-auth=`/`--jobserver-fds=` flags to some other `make`
+        // (BSD's bmake, Windows' nmake, …) makes it choke on an option it
+        // doesn't understand, so only enable it once we've confirmed the
+        // resolved `make` actually speaks GNU make.
+        let jobserver_supported = self.jobserver && make_is_gnu(&executable);
         if target.contains("emscripten") {
             program = "emmake";
             cmd = new_command("emmake");
-            cmd.arg(executable);
+            cmd.arg(&executable);
         } else {
-            cmd = new_command(executable);
+            cmd = new_command(&executable);
         }
         cmd.current_dir(&build);
 
-        let mut makeflags = None;
         let mut make_args = Vec::new();
 
         if let Some(args) = &self.make_args {
             make_args.extend_from_slice(args);
         }
 
-        if let Ok(num_jobs_s) = env::var("NUM_JOBS") {
-            // This looks like `make`, let's hope it understands `-jN`.
-            make_args.push(format!("-j{}", num_jobs_s));
-            match env::var_os("CARGO_MAKEFLAGS") {
-                // Only do this on non-windows and non-bsd
-                // On Windows, we could be invoking make instead of
-                // mingw32-make which doesn't work with our jobserver
-                // bsdmake also does not work with our job server
-                Some(ref cargo_make_flags)
-                    if !(cfg!(windows)
-                        || cfg!(target_os = "openbsd")
-                        || cfg!(target_os = "netbsd")
-                        || cfg!(target_os = "freebsd")
-                        || cfg!(target_os = "bitrig")
-                        || cfg!(target_os = "dragonflybsd")) =>
-                {
-                    makeflags = Some(cargo_make_flags.clone())
-                }
-                _ => (),
+        // Cargo hands build scripts its jobserver token pool via
+        // `CARGO_MAKEFLAGS` rather than the usual `MAKEFLAGS` (to avoid this
+        // very process picking it up by accident); mirror it into this
+        // process's `MAKEFLAGS` so `jobserver::Client::from_env` can find it.
+        let jobserver_client = if jobserver_supported {
+            if let Some(cargo_makeflags) = env::var_os("CARGO_MAKEFLAGS") {
+                env::set_var("MAKEFLAGS", cargo_makeflags);
             }
+            let inherited = unsafe { jobserver::Client::from_env() };
+            inherited.or_else(|| {
+                let num_jobs = env::var("NUM_JOBS")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(1);
+                jobserver::Client::new(num_jobs).ok()
+            })
+        } else {
+            None
+        };
+
+        if let Some(client) = &jobserver_client {
+            // Materializes the right `--jobserver-auth=`/`--jobserver-fds=`
+            // MAKEFLAGS for the platform and inherits the backing fds/handle
+            // into the child, so the sub-`make` cooperates with Cargo's
+            // global job limit instead of oversubscribing or serializing.
+            client.configure_make(&mut cmd);
+        } else if let Ok(num_jobs_s) = env::var("NUM_JOBS") {
+            // No jobserver token to inherit: fall back to an explicit `-jN`.
+            // Never add this *alongside* a jobserver token, or we'd
+            // double-count jobs.
+            make_args.push(format!("-j{}", num_jobs_s));
         }
 
         // And build!
         let make_targets = self.make_targets.get_or_insert(vec!["install".to_string()]);
-        if let Some(flags) = makeflags {
-            cmd.env("MAKEFLAGS", flags);
-        }
 
         try_run(
             cmd.args(make_targets).args(&make_args).current_dir(&build),
             program,
         )?;
 
-        println!("cargo:root={}", dst.display());
-        Ok(dst)
+        self.finish_build(dst)
     }
 
-    fn maybe_clear(&self, _dir: &Path) {
-        // TODO: make clean?
+    fn maybe_clear(&self, dir: &Path) -> Result<(), String> {
+        // `try_get_paths` (and thus this function) runs several times over
+        // the course of a single `build()`; only the first call gets to
+        // decide whether to clean, so a reused `OUT_DIR` isn't `make
+        // clean`ed repeatedly -- including, wrongly, after `configure` has
+        // already regenerated the Makefile.
+        if self.cleaned.replace(true) {
+            return Ok(());
+        }
+
+        if self.clean_policy == CleanPolicy::Never || !dir.join("Makefile").exists() {
+            return Ok(());
+        }
+
+        let should_clean = match self.clean_policy {
+            CleanPolicy::Never => unreachable!(),
+            CleanPolicy::Always => true,
+            CleanPolicy::OnSourceChange => {
+                let (_, hash) = fingerprint_source(&self.path, dir);
+                let fingerprint_file = dir.join("clean.fingerprint");
+                let unchanged = fs::read_to_string(&fingerprint_file)
+                    .ok()
+                    .and_then(|s| s.trim().parse::<u64>().ok())
+                    == Some(hash);
+                fs::write(&fingerprint_file, hash.to_string())
+                    .map_err(|e| format!("failed to write {}: {}", fingerprint_file.display(), e))?;
+                !unchanged
+            }
+        };
+
+        if should_clean {
+            let executable = env::var("MAKE").unwrap_or_else(|_| "make".to_owned());
+            let mut cmd = new_command(executable);
+            cmd.current_dir(dir).arg("clean");
+            try_run(&mut cmd, "make")?;
+        }
+
+        Ok(())
     }
 }
 
@@ -779,6 +1339,328 @@ fn new_command<S: AsRef<OsStr>>(program: S) -> Command {
     cmd
 }
 
+// Runs `<make> --version` and checks for the GNU Make banner.
+fn make_is_gnu(executable: &str) -> bool {
+    new_command(executable)
+        .arg("--version")
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).contains("GNU Make"))
+        .unwrap_or(false)
+}
+
+// Query the `.pc` file a just-built package installs into `$dst/lib/pkgconfig`
+// and translate the `-L`/`-l` flags `pkg-config` reports into the
+// corresponding `cargo:rustc-link-*` lines. Passing `--static` makes
+// `pkg-config` expand `Libs.private` and walk `Requires.private`
+// transitively, which is what picks up private dependencies that a plain
+// `.statik()` link would otherwise miss.
+fn emit_pkg_config_metadata(dst: &Path, name: &str, is_static: bool) -> Result<(), String> {
+    let pkgconfig_dir = dst.join("lib").join("pkgconfig");
+
+    let mut pkg_config_path = OsString::from(&pkgconfig_dir);
+    if let Some(existing) = env::var_os("PKG_CONFIG_PATH") {
+        pkg_config_path.push(":");
+        pkg_config_path.push(existing);
+    }
+
+    emit_pkg_config_libs(name, Some(&pkg_config_path), is_static)
+}
+
+// Runs the real `pkg-config` binary for `name` and translates its `--libs`
+// output into cargo link directives. Delegating to `pkg-config` (rather than
+// hand-parsing `.pc` files) gets us its `${variable}` expansion and
+// `Requires`/`Requires.private` transitive resolution for free.
+//
+// `pkg_config_path` overrides `PKG_CONFIG_PATH` for this invocation only; pass
+// `None` to inherit whatever is already set in the environment (e.g. by a
+// caller that exported it up front for a batch of lookups).
+fn emit_pkg_config_libs(
+    name: &str,
+    pkg_config_path: Option<&OsStr>,
+    is_static: bool,
+) -> Result<(), String> {
+    let mut cmd = Command::new("pkg-config");
+    if let Some(path) = pkg_config_path {
+        cmd.env("PKG_CONFIG_PATH", path);
+    }
+    cmd.arg("--libs").arg(name);
+    if is_static {
+        cmd.arg("--static");
+    }
+
+    let output = cmd
+        .output()
+        .map_err(|e| format!("failed to run `pkg-config` for `{}`: {}", name, e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "`pkg-config --libs{} {}` did not execute successfully, got: {}",
+            if is_static { " --static" } else { "" },
+            name,
+            output.status
+        ));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut flags = stdout.split_whitespace();
+    while let Some(flag) = flags.next() {
+        if flag == "-framework" {
+            // macOS: `-framework Name` is two tokens; translate to the
+            // `framework=` link-kind cargo understands.
+            if let Some(name) = flags.next() {
+                println!("cargo:rustc-link-lib=framework={}", name);
+            }
+        } else if let Some(path) = flag.strip_prefix("-F") {
+            println!("cargo:rustc-link-search=framework={}", path);
+        } else if let Some(path) = flag.strip_prefix("-L") {
+            println!("cargo:rustc-link-search=native={}", path);
+        } else if let Some(lib) = flag.strip_prefix("-l") {
+            // `pkg-config --static` reports the package's own library
+            // alongside its transitive deps with no distinction between
+            // them; only the package's own lib is guaranteed to have been
+            // built static by `--disable-shared`; libs pulled in from
+            // `Libs.private`/`Requires.private` are ordinary system
+            // libraries and usually only exist as shared objects.
+            if is_static && is_own_lib(lib, name) {
+                println!("cargo:rustc-link-lib=static={}", lib);
+            } else {
+                println!("cargo:rustc-link-lib={}", lib);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// A `.pc` file's own `-l` token is usually the package name with its `lib`
+// prefix stripped (`libxmp.pc` -> `-lxmp`), but some packages keep the
+// prefix (`libfoo.pc` -> `-llibfoo`); accept either spelling.
+fn is_own_lib(lib: &str, pkg_config_name: &str) -> bool {
+    lib == pkg_config_name || Some(lib) == pkg_config_name.strip_prefix("lib")
+}
+
+// Scans every `.pc` file under `$dst/lib/pkgconfig` and `$dst/lib64/pkgconfig`
+// and resolves each through `pkg-config` itself, falling back to globbing
+// `lib` for `lib<name>.a`/`lib<name>.so` when no `.pc` file is found at all.
+fn emit_metadata_for_prefix(dst: &Path, is_static: bool) -> Result<(), String> {
+    let pc_dirs: Vec<PathBuf> = ["lib", "lib64"]
+        .iter()
+        .map(|libdir| dst.join(libdir).join("pkgconfig"))
+        .filter(|dir| dir.is_dir())
+        .collect();
+
+    if let Some(first) = pc_dirs.first() {
+        let mut pkg_config_path = OsString::from(first);
+        for dir in &pc_dirs[1..] {
+            pkg_config_path.push(":");
+            pkg_config_path.push(dir);
+        }
+        if let Some(existing) = env::var_os("PKG_CONFIG_PATH") {
+            pkg_config_path.push(":");
+            pkg_config_path.push(existing);
+        }
+        env::set_var("PKG_CONFIG_PATH", pkg_config_path);
+    }
+
+    let mut pc_files = Vec::new();
+    for dir in &pc_dirs {
+        let entries = fs::read_dir(dir)
+            .map_err(|e| format!("failed to read {}: {}", dir.display(), e))?;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension() == Some(OsStr::new("pc")) {
+                pc_files.push(path);
+            }
+        }
+    }
+
+    if pc_files.is_empty() {
+        return emit_fallback_link_metadata(dst, is_static);
+    }
+
+    for pc_file in pc_files {
+        emit_pc_file_metadata(&pc_file, is_static)?;
+    }
+    Ok(())
+}
+
+// `pkg_config_path` must already be exported (via `env::set_var`) by the
+// caller so `pkg-config` can find `pc_file`'s package and whatever it
+// `Requires`; we only need it here to recover the package name to query.
+fn emit_pc_file_metadata(pc_file: &Path, is_static: bool) -> Result<(), String> {
+    let name = match pc_file.file_stem().and_then(OsStr::to_str) {
+        Some(name) => name,
+        None => {
+            return Err(format!(
+                "{} does not have a valid UTF-8 file name",
+                pc_file.display()
+            ))
+        }
+    };
+
+    emit_pkg_config_libs(name, None, is_static)
+}
+
+fn emit_fallback_link_metadata(dst: &Path, is_static: bool) -> Result<(), String> {
+    let lib_dir = dst.join("lib");
+    let entries = match fs::read_dir(&lib_dir) {
+        Ok(entries) => entries,
+        Err(_) => return Ok(()),
+    };
+
+    println!("cargo:rustc-link-search=native={}", lib_dir.display());
+    for entry in entries.flatten() {
+        let file_name = entry.file_name();
+        let file_name = file_name.to_string_lossy();
+        let stem = match file_name.strip_prefix("lib") {
+            Some(stem) => stem,
+            None => continue,
+        };
+        let name = stem
+            .strip_suffix(".a")
+            .or_else(|| stem.strip_suffix(".dylib"))
+            .or_else(|| stem.split(".so").next().filter(|_| stem.contains(".so")));
+        if let Some(name) = name {
+            if is_static {
+                println!("cargo:rustc-link-lib=static={}", name);
+            } else {
+                println!("cargo:rustc-link-lib={}", name);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// Probe for a system copy of `name` (at least `version`, if given) via
+// `pkg-config`. On success, this has already printed the library's
+// `cargo:rustc-link-*` metadata (the `pkg_config` crate does so by default)
+// and we just need a directory to hand back as the `Artifacts` root; on
+// failure we return `Ok(None)` so the caller falls back to the source build.
+fn try_system_probe(
+    name: &str,
+    version: Option<&str>,
+) -> Result<Option<(PathBuf, PathBuf, PathBuf)>, String> {
+    let mut cfg = pkg_config::Config::new();
+    if let Some(version) = version {
+        cfg.atleast_version(version);
+    }
+    match cfg.probe(name) {
+        Ok(lib) => {
+            let lib_dir = lib.link_paths.first().cloned().unwrap_or_default();
+            let include_dir = lib.include_paths.first().cloned().unwrap_or_default();
+            let root = lib_dir.parent().map(Path::to_path_buf).unwrap_or_default();
+
+            println!("cargo:root={}", root.display());
+            println!("cargo:include={}", include_dir.display());
+
+            Ok(Some((root, include_dir, lib_dir)))
+        }
+        Err(_) => Ok(None),
+    }
+}
+
+// Recursively collects every file under `root` (skipping the out-of-source
+// `build_dir` and `.git`) and folds their path, size and mtime into a single
+// hash, so `try_build` can tell whether anything that could affect the
+// compiled output has changed since the last run.
+fn fingerprint_source(root: &Path, build_dir: &Path) -> (Vec<PathBuf>, u64) {
+    let mut files = Vec::new();
+    walk_source(root, build_dir, &mut files);
+    files.sort();
+
+    let mut hasher = DefaultHasher::new();
+    for file in &files {
+        file.hash(&mut hasher);
+        if let Ok(meta) = fs::metadata(file) {
+            meta.len().hash(&mut hasher);
+            if let Ok(modified) = meta.modified() {
+                if let Ok(since_epoch) = modified.duration_since(UNIX_EPOCH) {
+                    since_epoch.as_secs().hash(&mut hasher);
+                    since_epoch.subsec_nanos().hash(&mut hasher);
+                }
+            }
+        }
+    }
+
+    (files, hasher.finish())
+}
+
+fn walk_source(dir: &Path, build_dir: &Path, out: &mut Vec<PathBuf>) {
+    if dir == build_dir {
+        return;
+    }
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path == build_dir || path.file_name() == Some(OsStr::new(".git")) {
+            continue;
+        }
+        if path.is_dir() {
+            walk_source(&path, build_dir, out);
+        } else {
+            out.push(path);
+        }
+    }
+}
+
+// Collects the subset of `fingerprint_source`'s file list that's actually
+// worth telling Cargo about: the autotools build description and the C/C++
+// sources it compiles. This follows the `rsconf::rebuild_if_path_changed`
+// pattern of only emitting `rerun-if-changed` for inputs that plausibly
+// affect the build's output, rather than every file under the tree.
+fn autotools_source_files(root: &Path, build_dir: &Path) -> Vec<PathBuf> {
+    const NAMES: &[&str] = &[
+        "configure.ac",
+        "configure.in",
+        "configure",
+        "Makefile.am",
+        "Makefile.in",
+    ];
+    const EXTENSIONS: &[&str] = &["c", "h", "cc", "cpp", "cxx", "hpp", "hxx"];
+
+    let (files, _) = fingerprint_source(root, build_dir);
+    files
+        .into_iter()
+        .filter(|path| {
+            let is_tracked_name = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .map(|n| NAMES.contains(&n))
+                .unwrap_or(false);
+            let is_tracked_ext = path
+                .extension()
+                .and_then(|e| e.to_str())
+                .map(|e| EXTENSIONS.contains(&e))
+                .unwrap_or(false);
+            is_tracked_name || is_tracked_ext
+        })
+        .collect()
+}
+
+// Resolves a binutil (`ar`, `ranlib`, `nm`, `strip`, `ld`) with priority:
+// explicit builder override > `$<env_var>` from the environment >
+// `<target>-<default_name>` when cross-compiling > plain `<default_name>`.
+fn resolve_binutil(
+    explicit: &Option<OsString>,
+    env_var: &str,
+    cross_prefix: Option<&String>,
+    default_name: &str,
+) -> OsString {
+    if let Some(explicit) = explicit {
+        return explicit.clone();
+    }
+    if let Some(from_env) = env::var_os(env_var) {
+        return from_env;
+    }
+    match cross_prefix {
+        Some(prefix) => OsString::from(format!("{}-{}", prefix, default_name)),
+        None => OsString::from(default_name),
+    }
+}
+
 fn try_getenv_unwrap(v: &str) -> Result<String, String> {
     match env::var(v) {
         Ok(s) => Ok(s),